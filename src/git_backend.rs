@@ -0,0 +1,440 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+/// Ahead/behind and dirty-state summary for a worktree, as reported by `git status`.
+pub struct GitStatus {
+    pub ahead: i64,
+    pub behind: i64,
+    pub tracked: u32,
+    pub untracked: u32,
+}
+
+/// A local branch and the commit time of its tip, used to sort the branch picker.
+pub struct BranchInfo {
+    pub name: String,
+    pub commit_ts: i64,
+}
+
+/// One entry from git's own worktree list, used by `sprout sync` to reconcile
+/// against `metadata.json`. `branch` is `None` for a detached-HEAD worktree.
+pub struct GitWorktreeRecord {
+    pub path: String,
+    pub branch: Option<String>,
+}
+
+/// Abstracts the git operations sprout needs so they can be backed either by
+/// shelling out to the `git` binary or by talking to libgit2 directly.
+/// `Send + Sync` so a single backend can be shared across the refresh threads
+/// that `list_worktrees` dispatches.
+pub trait GitBackend: Send + Sync {
+    fn repo_root(&self) -> Result<PathBuf>;
+    /// Create a new branch at `base` and add a worktree checking it out.
+    fn add_worktree_from(&self, repo: &Path, path: &Path, branch: &str, base: &str) -> Result<()>;
+    /// Check out `reference` into a new worktree. If it's an existing local branch it's
+    /// checked out directly; otherwise it's treated as a remote-tracking branch and a new
+    /// local branch is created to track it. Returns the local branch name actually checked out.
+    fn checkout_worktree(&self, repo: &Path, path: &Path, reference: &str) -> Result<String>;
+    fn remove_worktree(&self, repo: &Path, path: &str) -> Result<()>;
+    /// Like `remove_worktree`, but discards any uncommitted/untracked contents instead of
+    /// refusing. Used only to roll back a worktree that failed provisioning partway through.
+    fn remove_worktree_force(&self, repo: &Path, path: &str) -> Result<()>;
+    fn last_commit_ts(&self, worktree_path: &str) -> Result<i64>;
+    fn status(&self, worktree_path: &str) -> Result<GitStatus>;
+    fn list_branches(&self, repo: &Path) -> Result<Vec<BranchInfo>>;
+    fn list_git_worktrees(&self, repo: &Path) -> Result<Vec<GitWorktreeRecord>>;
+}
+
+/// Default backend: spawns the external `git` binary for every operation.
+/// Slower across many worktrees but has no library dependency beyond `git`
+/// being on `PATH`.
+pub struct ProcessBackend;
+
+impl GitBackend for ProcessBackend {
+    fn repo_root(&self) -> Result<PathBuf> {
+        let output = Command::new("git")
+            .args(["rev-parse", "--show-toplevel"])
+            .stdout(Stdio::piped())
+            .output()
+            .context("failed to run git")?;
+        if !output.status.success() {
+            bail!("not in a git repository");
+        }
+        let text = String::from_utf8_lossy(&output.stdout);
+        Ok(PathBuf::from(text.trim()))
+    }
+
+    fn add_worktree_from(&self, repo: &Path, path: &Path, branch: &str, base: &str) -> Result<()> {
+        run_git(
+            repo,
+            &[
+                "worktree",
+                "add",
+                "-b",
+                branch,
+                path.to_str().ok_or_else(|| anyhow!("invalid path"))?,
+                base,
+            ],
+        )
+    }
+
+    fn checkout_worktree(&self, repo: &Path, path: &Path, reference: &str) -> Result<String> {
+        let path_str = path.to_str().ok_or_else(|| anyhow!("invalid path"))?;
+
+        if local_branch_exists(repo, reference)? {
+            run_git(repo, &["worktree", "add", path_str, reference])?;
+            return Ok(reference.to_string());
+        }
+
+        // Not a local branch: treat it as a remote-tracking ref (e.g. `origin/feature`)
+        // and create a local branch tracking it, since `git worktree add` won't DWIM that.
+        let local_name = reference.rsplit('/').next().unwrap_or(reference);
+        run_git(
+            repo,
+            &[
+                "worktree", "add", "--track", "-b", local_name, path_str, reference,
+            ],
+        )?;
+        Ok(local_name.to_string())
+    }
+
+    fn remove_worktree(&self, repo: &Path, path: &str) -> Result<()> {
+        run_git(repo, &["worktree", "remove", path])
+    }
+
+    fn remove_worktree_force(&self, repo: &Path, path: &str) -> Result<()> {
+        run_git(repo, &["worktree", "remove", "--force", path])
+    }
+
+    fn last_commit_ts(&self, worktree_path: &str) -> Result<i64> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(worktree_path)
+            .args(["log", "-1", "--format=%ct"])
+            .stdout(Stdio::piped())
+            .output()
+            .context("failed to run git")?;
+        if !output.status.success() {
+            return Ok(0);
+        }
+        let text = String::from_utf8_lossy(&output.stdout);
+        Ok(text.trim().parse::<i64>().unwrap_or(0))
+    }
+
+    fn status(&self, worktree_path: &str) -> Result<GitStatus> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(worktree_path)
+            .args(["status", "--porcelain=v2", "--branch"])
+            .stdout(Stdio::piped())
+            .output()
+            .context("failed to run git")?;
+        if !output.status.success() {
+            bail!("git status failed");
+        }
+
+        let text = String::from_utf8_lossy(&output.stdout);
+        let mut status = GitStatus {
+            ahead: 0,
+            behind: 0,
+            tracked: 0,
+            untracked: 0,
+        };
+        for line in text.lines() {
+            if let Some(rest) = line.strip_prefix("# branch.ab ") {
+                for part in rest.split_whitespace() {
+                    if let Some(n) = part.strip_prefix('+') {
+                        status.ahead = n.parse().unwrap_or(0);
+                    } else if let Some(n) = part.strip_prefix('-') {
+                        status.behind = n.parse().unwrap_or(0);
+                    }
+                }
+            } else if line.starts_with("1 ") || line.starts_with("2 ") {
+                status.tracked += 1;
+            } else if line.starts_with("? ") {
+                status.untracked += 1;
+            }
+        }
+        Ok(status)
+    }
+
+    fn list_branches(&self, repo: &Path) -> Result<Vec<BranchInfo>> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args([
+                "for-each-ref",
+                "--format=%(refname:short) %(committerdate:unix)",
+                "refs/heads/",
+            ])
+            .stdout(Stdio::piped())
+            .output()
+            .context("failed to run git")?;
+        if !output.status.success() {
+            bail!("git for-each-ref failed");
+        }
+
+        let text = String::from_utf8_lossy(&output.stdout);
+        let mut branches = Vec::new();
+        for line in text.lines() {
+            let Some((name, ts)) = line.rsplit_once(' ') else {
+                continue;
+            };
+            branches.push(BranchInfo {
+                name: name.to_string(),
+                commit_ts: ts.parse().unwrap_or(0),
+            });
+        }
+        Ok(branches)
+    }
+
+    fn list_git_worktrees(&self, repo: &Path) -> Result<Vec<GitWorktreeRecord>> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args(["worktree", "list", "--porcelain"])
+            .stdout(Stdio::piped())
+            .output()
+            .context("failed to run git")?;
+        if !output.status.success() {
+            bail!("git worktree list failed");
+        }
+
+        let text = String::from_utf8_lossy(&output.stdout);
+        let mut records = Vec::new();
+        let mut path: Option<String> = None;
+        let mut branch: Option<String> = None;
+
+        for line in text.lines() {
+            if let Some(p) = line.strip_prefix("worktree ") {
+                if let Some(path) = path.take() {
+                    records.push(GitWorktreeRecord {
+                        path,
+                        branch: branch.take(),
+                    });
+                }
+                path = Some(p.to_string());
+            } else if let Some(b) = line.strip_prefix("branch ") {
+                branch = Some(b.trim_start_matches("refs/heads/").to_string());
+            } else if line == "detached" {
+                branch = None;
+            }
+        }
+        if let Some(path) = path {
+            records.push(GitWorktreeRecord { path, branch });
+        }
+
+        Ok(records)
+    }
+}
+
+fn run_git(repo: &Path, args: &[&str]) -> Result<()> {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .status()
+        .context("failed to run git")?;
+    if !status.success() {
+        bail!("git command failed");
+    }
+    Ok(())
+}
+
+fn local_branch_exists(repo: &Path, name: &str) -> Result<bool> {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["show-ref", "--verify", "--quiet", &format!("refs/heads/{name}")])
+        .status()
+        .context("failed to run git")?;
+    Ok(status.success())
+}
+
+/// libgit2-backed implementation, selected when `git_backend = "libgit2"` is
+/// set in config. Avoids spawning a `git` subprocess per call.
+pub struct LibGitBackend;
+
+impl GitBackend for LibGitBackend {
+    fn repo_root(&self) -> Result<PathBuf> {
+        let repo = git2::Repository::discover(".").context("not in a git repository")?;
+        let root = repo
+            .workdir()
+            .ok_or_else(|| anyhow!("repository has no working directory"))?;
+        Ok(root.to_path_buf())
+    }
+
+    fn add_worktree_from(&self, repo: &Path, path: &Path, branch: &str, base: &str) -> Result<()> {
+        let repo = git2::Repository::open(repo).context("failed to open repository")?;
+        let base_commit = repo
+            .revparse_single(base)
+            .context("failed to resolve base ref")?
+            .peel_to_commit()?;
+        let branch_ref = repo.branch(branch, &base_commit, false)?.into_reference();
+        let mut opts = git2::WorktreeAddOptions::new();
+        opts.reference(Some(&branch_ref));
+        repo.worktree(branch, path, Some(&opts))
+            .context("failed to add worktree")?;
+        Ok(())
+    }
+
+    fn checkout_worktree(&self, repo: &Path, path: &Path, reference: &str) -> Result<String> {
+        let repo = git2::Repository::open(repo).context("failed to open repository")?;
+
+        if let Ok(branch) = repo.find_branch(reference, git2::BranchType::Local) {
+            let git_ref = branch.into_reference();
+            let mut opts = git2::WorktreeAddOptions::new();
+            opts.reference(Some(&git_ref));
+            repo.worktree(reference, path, Some(&opts))
+                .context("failed to add worktree")?;
+            return Ok(reference.to_string());
+        }
+
+        // Not a local branch: treat it as a remote-tracking ref (e.g. `origin/feature`)
+        // and create a local branch tracking it, mirroring the process backend.
+        let remote_branch = repo
+            .find_branch(reference, git2::BranchType::Remote)
+            .with_context(|| format!("no local or remote-tracking branch named {reference}"))?;
+        let target = remote_branch
+            .get()
+            .peel_to_commit()
+            .context("remote branch has no commit")?;
+        let local_name = reference.rsplit('/').next().unwrap_or(reference);
+        let mut local_branch = repo.branch(local_name, &target, false)?;
+        local_branch
+            .set_upstream(Some(reference))
+            .context("failed to set upstream")?;
+        let git_ref = local_branch.into_reference();
+        let mut opts = git2::WorktreeAddOptions::new();
+        opts.reference(Some(&git_ref));
+        repo.worktree(local_name, path, Some(&opts))
+            .context("failed to add worktree")?;
+        Ok(local_name.to_string())
+    }
+
+    fn remove_worktree(&self, repo: &Path, path: &str) -> Result<()> {
+        let status = self.status(path)?;
+        if status.tracked + status.untracked > 0 {
+            bail!("worktree contains modified or untracked files, use force removal: {path}");
+        }
+
+        let repo = git2::Repository::open(repo).context("failed to open repository")?;
+        for name in repo.worktrees()?.iter().flatten() {
+            let worktree = repo.find_worktree(name)?;
+            if worktree.path() == Path::new(path) {
+                // The worktree directory is still present (we just checked it for dirty
+                // state above), so it must be marked `valid` or libgit2 refuses to prune
+                // it; `working_tree` makes the prune delete the directory as well.
+                let mut opts = git2::WorktreePruneOptions::new();
+                opts.valid(true).working_tree(true);
+                worktree
+                    .prune(Some(&mut opts))
+                    .context("failed to prune worktree")?;
+                return Ok(());
+            }
+        }
+        bail!("worktree not known to git: {path}");
+    }
+
+    fn remove_worktree_force(&self, repo: &Path, path: &str) -> Result<()> {
+        let repo = git2::Repository::open(repo).context("failed to open repository")?;
+        for name in repo.worktrees()?.iter().flatten() {
+            let worktree = repo.find_worktree(name)?;
+            if worktree.path() == Path::new(path) {
+                let mut opts = git2::WorktreePruneOptions::new();
+                opts.valid(true).working_tree(true);
+                worktree
+                    .prune(Some(&mut opts))
+                    .context("failed to force-remove worktree")?;
+                return Ok(());
+            }
+        }
+        bail!("worktree not known to git: {path}");
+    }
+
+    fn last_commit_ts(&self, worktree_path: &str) -> Result<i64> {
+        let repo = git2::Repository::open(worktree_path).context("failed to open repository")?;
+        let commit = repo.head()?.peel_to_commit()?;
+        Ok(commit.time().seconds())
+    }
+
+    fn status(&self, worktree_path: &str) -> Result<GitStatus> {
+        let repo = git2::Repository::open(worktree_path).context("failed to open repository")?;
+        let mut opts = git2::StatusOptions::new();
+        opts.include_untracked(true);
+        let statuses = repo.statuses(Some(&mut opts))?;
+
+        let mut tracked = 0;
+        let mut untracked = 0;
+        for entry in statuses.iter() {
+            if entry.status().intersects(git2::Status::WT_NEW) {
+                untracked += 1;
+            } else {
+                tracked += 1;
+            }
+        }
+
+        let (ahead, behind) = branch_ahead_behind(&repo).unwrap_or((0, 0));
+
+        Ok(GitStatus {
+            ahead,
+            behind,
+            tracked,
+            untracked,
+        })
+    }
+
+    fn list_branches(&self, repo: &Path) -> Result<Vec<BranchInfo>> {
+        let repo = git2::Repository::open(repo).context("failed to open repository")?;
+        let mut branches = Vec::new();
+        for item in repo.branches(Some(git2::BranchType::Local))? {
+            let (branch, _) = item?;
+            let Some(name) = branch.name()?.map(str::to_string) else {
+                continue;
+            };
+            let commit_ts = branch
+                .get()
+                .peel_to_commit()
+                .map(|commit| commit.time().seconds())
+                .unwrap_or(0);
+            branches.push(BranchInfo { name, commit_ts });
+        }
+        Ok(branches)
+    }
+
+    fn list_git_worktrees(&self, repo: &Path) -> Result<Vec<GitWorktreeRecord>> {
+        let repo = git2::Repository::open(repo).context("failed to open repository")?;
+        let mut records = Vec::new();
+        for name in repo.worktrees()?.iter().flatten() {
+            let worktree = repo.find_worktree(name)?;
+            let path = worktree.path().to_string_lossy().to_string();
+            let branch = git2::Repository::open_from_worktree(&worktree)
+                .ok()
+                .and_then(|wt_repo| {
+                    if wt_repo.head_detached().unwrap_or(false) {
+                        return None;
+                    }
+                    wt_repo
+                        .head()
+                        .ok()
+                        .and_then(|head| head.shorthand().map(str::to_string))
+                });
+            records.push(GitWorktreeRecord { path, branch });
+        }
+        Ok(records)
+    }
+}
+
+fn branch_ahead_behind(repo: &git2::Repository) -> Result<(i64, i64)> {
+    let head = repo.head()?;
+    let name = head.name().ok_or_else(|| anyhow!("detached HEAD"))?;
+    let local = head.target().ok_or_else(|| anyhow!("unborn branch"))?;
+    let upstream_name = repo.branch_upstream_name(name)?;
+    let upstream = repo
+        .find_reference(upstream_name.as_str().ok_or_else(|| anyhow!("invalid upstream name"))?)?
+        .target()
+        .ok_or_else(|| anyhow!("upstream has no target"))?;
+    let (ahead, behind) = repo.graph_ahead_behind(local, upstream)?;
+    Ok((ahead as i64, behind as i64))
+}
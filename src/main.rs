@@ -1,7 +1,8 @@
+use std::collections::HashMap;
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
+use std::process::Command;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use anyhow::{anyhow, bail, Context, Result};
@@ -10,6 +11,10 @@ use clap::{Parser, Subcommand};
 use comfy_table::{Cell, Table};
 use serde::{Deserialize, Serialize};
 
+mod git_backend;
+
+use git_backend::{BranchInfo, GitBackend, GitStatus, GitWorktreeRecord, LibGitBackend, ProcessBackend};
+
 #[derive(Parser)]
 #[command(name = "sprout", version, about = "Minimal git worktree manager")]
 struct Cli {
@@ -19,11 +24,22 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Commands {
-    Create { worktree: String },
+    Create {
+        worktree: String,
+        /// Base the new branch/worktree on this commit, branch, or tag instead of HEAD.
+        #[arg(long, conflicts_with = "track")]
+        from: Option<String>,
+        /// Check out this existing local branch; a remote-tracking ref gets a new
+        /// local branch that tracks it.
+        #[arg(long, conflicts_with = "from")]
+        track: Option<String>,
+    },
     Cd { worktree: String },
     Base,
     List,
     Ls,
+    Branches,
+    Sync,
     Delete { worktree: String },
     Config {
         #[command(subcommand)]
@@ -40,6 +56,13 @@ enum ConfigAction {
 #[derive(Deserialize, Serialize, Default)]
 struct Config {
     branch_prefix: Option<String>,
+    git_backend: Option<String>,
+    /// Glob patterns, relative to the source repo root, copied into every new worktree.
+    #[serde(default)]
+    copy_globs: Vec<String>,
+    /// Shell commands run in the new worktree's directory before the shell is launched.
+    #[serde(default)]
+    post_create_hooks: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -48,9 +71,15 @@ struct WorktreeEntry {
     path: String,
     source_repo: String,
     branch: String,
+    #[serde(default = "default_base")]
+    base: String,
     created_at: i64,
 }
 
+fn default_base() -> String {
+    "HEAD".to_string()
+}
+
 #[derive(Serialize, Deserialize, Default)]
 struct Metadata {
     worktrees: Vec<WorktreeEntry>,
@@ -59,18 +88,25 @@ struct Metadata {
 fn main() -> Result<()> {
     let cli = Cli::parse();
     match cli.command {
-        Commands::Create { worktree } => create_worktree(&worktree),
+        Commands::Create { worktree, from, track } => {
+            create_worktree(&worktree, from.as_deref(), track.as_deref())
+        }
         Commands::Cd { worktree } => cd_worktree(&worktree),
         Commands::Base => cd_base(),
         Commands::List | Commands::Ls => list_worktrees(),
+        Commands::Branches => list_branches(),
+        Commands::Sync => sync_metadata(),
         Commands::Delete { worktree } => delete_worktree(&worktree),
         Commands::Config { action } => config_cmd(action),
     }
 }
 
-fn create_worktree(name: &str) -> Result<()> {
-    let repo_root = fs::canonicalize(git_repo_root()?)?;
+fn create_worktree(name: &str, from: Option<&str>, track: Option<&str>) -> Result<()> {
     let paths = sprout_paths()?;
+    let config = load_config(&paths.config_path)?;
+    let backend = select_backend(&config);
+
+    let repo_root = fs::canonicalize(backend.repo_root()?)?;
     fs::create_dir_all(&paths.worktrees_dir)?;
 
     let worktree_path = paths.worktrees_dir.join(name);
@@ -78,35 +114,47 @@ fn create_worktree(name: &str) -> Result<()> {
         bail!("worktree already exists at {}", worktree_path.display());
     }
 
-    let config = load_config(&paths.config_path)?;
     let mut metadata = load_metadata(&paths.metadata_path)?;
     if metadata.worktrees.iter().any(|entry| entry.name == name) {
         bail!("worktree name already exists: {}", name);
     }
-    let prefix = config.branch_prefix.unwrap_or_else(|| "sprout/".to_string());
-    let branch = if prefix.is_empty() {
-        name.to_string()
-    } else {
-        format!("{}{}", prefix, name)
-    };
 
-    run_git(
-        &repo_root,
-        &[
-            "worktree",
-            "add",
-            "-b",
-            &branch,
-            worktree_path.to_str().ok_or_else(|| anyhow!("invalid path"))?,
-        ],
-    )?;
+    let base = from.unwrap_or("HEAD").to_string();
+    let copy_globs = config.copy_globs.clone();
+    let post_create_hooks = config.post_create_hooks.clone();
+    let branch = match track {
+        Some(track_ref) => backend.checkout_worktree(&repo_root, &worktree_path, track_ref)?,
+        None => {
+            let prefix = config.branch_prefix.unwrap_or_else(|| "sprout/".to_string());
+            let branch = if prefix.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}{}", prefix, name)
+            };
+            backend.add_worktree_from(&repo_root, &worktree_path, &branch, &base)?;
+            branch
+        }
+    };
 
     let worktree_path = fs::canonicalize(&worktree_path)?;
+
+    if let Err(err) = provision_worktree(&repo_root, &worktree_path, &copy_globs, &post_create_hooks)
+    {
+        let worktree_path_str = worktree_path.to_string_lossy();
+        if let Err(cleanup_err) = backend.remove_worktree_force(&repo_root, &worktree_path_str) {
+            eprintln!(
+                "warning: failed to roll back half-provisioned worktree {worktree_path_str}: {cleanup_err}"
+            );
+        }
+        return Err(err);
+    }
+
     metadata.worktrees.push(WorktreeEntry {
         name: name.to_string(),
         path: worktree_path.to_string_lossy().to_string(),
         source_repo: repo_root.to_string_lossy().to_string(),
         branch,
+        base,
         created_at: now_ts()?,
     });
     save_metadata(&paths.metadata_path, &metadata)?;
@@ -114,6 +162,26 @@ fn create_worktree(name: &str) -> Result<()> {
     launch_shell(&worktree_path)
 }
 
+fn list_branches() -> Result<()> {
+    let paths = sprout_paths()?;
+    let config = load_config(&paths.config_path)?;
+    let backend = select_backend(&config);
+    let repo_root = fs::canonicalize(backend.repo_root()?)?;
+
+    let mut branches = backend.list_branches(&repo_root)?;
+    branches.sort_by(|a, b| b.commit_ts.cmp(&a.commit_ts));
+
+    let mut table = Table::new();
+    table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+    table.set_header(vec!["Branch", "Last Commit"]);
+    for BranchInfo { name, commit_ts } in branches {
+        table.add_row(vec![Cell::new(name), Cell::new(format_ts(commit_ts))]);
+    }
+
+    println!("{table}");
+    Ok(())
+}
+
 fn cd_worktree(name: &str) -> Result<()> {
     let paths = sprout_paths()?;
     let metadata = load_metadata(&paths.metadata_path)?;
@@ -127,8 +195,10 @@ fn cd_worktree(name: &str) -> Result<()> {
 }
 
 fn cd_base() -> Result<()> {
-    let repo_root = fs::canonicalize(git_repo_root()?)?;
     let paths = sprout_paths()?;
+    let config = load_config(&paths.config_path)?;
+    let backend = select_backend(&config);
+    let repo_root = fs::canonicalize(backend.repo_root()?)?;
     let metadata = load_metadata(&paths.metadata_path)?;
     let repo_root_str = repo_root.to_string_lossy();
     let entry = metadata.worktrees.iter().find(|entry| {
@@ -145,27 +215,28 @@ fn cd_base() -> Result<()> {
     }
 }
 
+/// Number of worktrees refreshed concurrently per batch in `list_worktrees`.
+const REFRESH_BATCH_SIZE: usize = 8;
+
 fn list_worktrees() -> Result<()> {
     let paths = sprout_paths()?;
+    let config = load_config(&paths.config_path)?;
+    let backend = select_backend(&config);
     let metadata = load_metadata(&paths.metadata_path)?;
-    let mut rows = Vec::new();
-
-    for entry in metadata.worktrees {
-        let last_commit = git_last_commit_ts(&entry.path).unwrap_or(0);
-        rows.push((last_commit, entry));
-    }
 
+    let mut rows = refresh_entries(backend.as_ref(), metadata.worktrees);
     rows.sort_by(|a, b| b.0.cmp(&a.0));
 
     let mut table = Table::new();
     table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
-    table.set_header(vec!["Name", "Repo", "Path", "Branch", "Last Commit"]);
-    for (ts, entry) in rows {
+    table.set_header(vec!["Name", "Repo", "Path", "Branch", "Status", "Last Commit"]);
+    for (ts, status, entry) in rows {
         table.add_row(vec![
             Cell::new(entry.name),
             Cell::new(entry.source_repo),
             Cell::new(entry.path),
             Cell::new(entry.branch),
+            Cell::new(status),
             Cell::new(format_ts(ts)),
         ]);
     }
@@ -174,8 +245,42 @@ fn list_worktrees() -> Result<()> {
     Ok(())
 }
 
+/// Fetches `(last_commit, status)` for every entry, dispatching the per-worktree
+/// git queries in fixed-size concurrent batches so large sets of worktrees stay
+/// responsive. Output order is unspecified; callers sort after collecting.
+fn refresh_entries(
+    backend: &dyn GitBackend,
+    entries: Vec<WorktreeEntry>,
+) -> Vec<(i64, String, WorktreeEntry)> {
+    let mut rows = Vec::with_capacity(entries.len());
+
+    for batch in entries.chunks(REFRESH_BATCH_SIZE) {
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = batch
+                .iter()
+                .map(|entry| {
+                    scope.spawn(move || {
+                        let last_commit = backend.last_commit_ts(&entry.path).unwrap_or(0);
+                        let status = format_status(backend.status(&entry.path).ok());
+                        (last_commit, status, entry.clone())
+                    })
+                })
+                .collect();
+            for handle in handles {
+                if let Ok(row) = handle.join() {
+                    rows.push(row);
+                }
+            }
+        });
+    }
+
+    rows
+}
+
 fn delete_worktree(name: &str) -> Result<()> {
     let paths = sprout_paths()?;
+    let config = load_config(&paths.config_path)?;
+    let backend = select_backend(&config);
     let mut metadata = load_metadata(&paths.metadata_path)?;
     let index = metadata
         .worktrees
@@ -184,53 +289,193 @@ fn delete_worktree(name: &str) -> Result<()> {
         .ok_or_else(|| anyhow!("unknown worktree: {}", name))?;
     let entry = metadata.worktrees.remove(index);
 
-    run_git(
-        Path::new(&entry.source_repo),
-        &["worktree", "remove", &entry.path],
-    )?;
+    backend.remove_worktree(Path::new(&entry.source_repo), &entry.path)?;
     save_metadata(&paths.metadata_path, &metadata)?;
     Ok(())
 }
 
-fn git_repo_root() -> Result<PathBuf> {
-    let output = Command::new("git")
-        .args(["rev-parse", "--show-toplevel"])
-        .stdout(Stdio::piped())
-        .output()
-        .context("failed to run git")?;
-    if !output.status.success() {
-        bail!("not in a git repository");
+/// Outcome of reconciling one `source_repo`'s metadata entries against git's view
+/// of its worktrees, as produced by [`reconcile_repo_worktrees`].
+#[derive(Default)]
+struct RepoReconcile {
+    kept: Vec<WorktreeEntry>,
+    removed: Vec<String>,
+    updated: Vec<String>,
+    imported: Vec<String>,
+}
+
+/// Pure reconciliation of a single `source_repo`'s metadata entries against
+/// `git worktree list`'s view: drops entries whose path is gone or unknown to
+/// git, imports worktrees git knows about that sprout doesn't, and refreshes
+/// stale `branch` values. Split out of `sync_metadata` so it can be unit
+/// tested without shelling out to git.
+fn reconcile_repo_worktrees(
+    repo: &str,
+    repo_canonical: &str,
+    git_worktrees: Vec<GitWorktreeRecord>,
+    entries: Vec<WorktreeEntry>,
+    now: i64,
+) -> RepoReconcile {
+    let mut result = RepoReconcile::default();
+
+    // `git worktree list` always includes the main worktree (the repo root itself),
+    // which sprout never tracks as one of its own entries — drop it before
+    // reconciling, or it gets "imported" as a bogus sprout-managed worktree.
+    let mut git_by_path: HashMap<String, Option<String>> = git_worktrees
+        .into_iter()
+        .filter(|record| {
+            canonicalize_string(&record.path).unwrap_or_else(|| record.path.clone())
+                != repo_canonical
+        })
+        .map(|record| (record.path, record.branch))
+        .collect();
+
+    for mut entry in entries {
+        let canonical = canonicalize_string(&entry.path);
+        let known = canonical
+            .as_deref()
+            .and_then(|path| git_by_path.remove(path))
+            .or_else(|| git_by_path.remove(&entry.path));
+
+        // Git may still report a worktree whose directory was removed by hand (it's
+        // merely flagged "prunable" until `git worktree prune` runs) — don't trust
+        // git's bookkeeping alone, check the path is actually still there.
+        if known.is_some() && !Path::new(&entry.path).exists() {
+            result.removed.push(entry.name);
+            continue;
+        }
+
+        match known {
+            None => result.removed.push(entry.name),
+            Some(branch) => {
+                if let Some(branch) = branch {
+                    if branch != entry.branch {
+                        result
+                            .updated
+                            .push(format!("{}: {} -> {}", entry.name, entry.branch, branch));
+                        entry.branch = branch;
+                    }
+                }
+                result.kept.push(entry);
+            }
+        }
+    }
+
+    for (path, branch) in git_by_path {
+        let name = branch.clone().unwrap_or_else(|| {
+            Path::new(&path)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.clone())
+        });
+        result.imported.push(format!("{name} ({path})"));
+        result.kept.push(WorktreeEntry {
+            name,
+            path,
+            source_repo: repo.to_string(),
+            branch: branch.unwrap_or_else(|| "HEAD".to_string()),
+            base: "HEAD".to_string(),
+            created_at: now,
+        });
     }
-    let text = String::from_utf8_lossy(&output.stdout);
-    Ok(PathBuf::from(text.trim()))
+
+    result
 }
 
-fn run_git(repo: &Path, args: &[&str]) -> Result<()> {
-    let status = Command::new("git")
-        .arg("-C")
-        .arg(repo)
-        .args(args)
-        .status()
-        .context("failed to run git")?;
-    if !status.success() {
-        bail!("git command failed");
+/// Reconciles `metadata.json` against what git actually knows, per `source_repo`:
+/// drops entries whose path is gone or unknown to git, imports worktrees git
+/// knows about that sprout doesn't, and refreshes stale `branch` values.
+fn sync_metadata() -> Result<()> {
+    let paths = sprout_paths()?;
+    let config = load_config(&paths.config_path)?;
+    let backend = select_backend(&config);
+    let mut metadata = load_metadata(&paths.metadata_path)?;
+
+    let mut source_repos: Vec<String> = metadata
+        .worktrees
+        .iter()
+        .map(|entry| entry.source_repo.clone())
+        .collect();
+    source_repos.sort();
+    source_repos.dedup();
+
+    let mut kept = Vec::new();
+    let mut removed = Vec::new();
+    let mut updated = Vec::new();
+    let mut imported = Vec::new();
+    let now = now_ts()?;
+
+    for repo in &source_repos {
+        let repo_canonical = canonicalize_string(repo).unwrap_or_else(|| repo.clone());
+        let git_worktrees = backend
+            .list_git_worktrees(Path::new(repo))
+            .unwrap_or_default();
+
+        let entries: Vec<WorktreeEntry> = metadata
+            .worktrees
+            .iter()
+            .filter(|entry| &entry.source_repo == repo)
+            .cloned()
+            .collect();
+
+        let mut reconciled =
+            reconcile_repo_worktrees(repo, &repo_canonical, git_worktrees, entries, now);
+        kept.append(&mut reconciled.kept);
+        removed.append(&mut reconciled.removed);
+        updated.append(&mut reconciled.updated);
+        imported.append(&mut reconciled.imported);
+    }
+
+    metadata.worktrees = kept;
+
+    for name in &removed {
+        println!("- removed {name}");
+    }
+    for change in &updated {
+        println!("~ updated {change}");
+    }
+    for entry in &imported {
+        println!("+ imported {entry}");
+    }
+    if removed.is_empty() && updated.is_empty() && imported.is_empty() {
+        println!("up to date");
+    }
+
+    save_metadata(&paths.metadata_path, &metadata)
+}
+
+fn select_backend(config: &Config) -> Box<dyn GitBackend> {
+    match config.git_backend.as_deref() {
+        Some("libgit2") => Box::new(LibGitBackend),
+        _ => Box::new(ProcessBackend),
     }
-    Ok(())
 }
 
-fn git_last_commit_ts(worktree_path: &str) -> Result<i64> {
-    let output = Command::new("git")
-        .arg("-C")
-        .arg(worktree_path)
-        .args(["log", "-1", "--format=%ct"])
-        .stdout(Stdio::piped())
-        .output()
-        .context("failed to run git")?;
-    if !output.status.success() {
-        return Ok(0);
+fn format_status(status: Option<GitStatus>) -> String {
+    let Some(status) = status else {
+        return "-".to_string();
+    };
+
+    let dirty = status.tracked + status.untracked;
+    let state = if dirty == 0 {
+        "\u{2713} clean".to_string()
+    } else {
+        format!("\u{25cf} {dirty}\u{00b1}")
+    };
+
+    let mut ab_parts = Vec::new();
+    if status.ahead > 0 {
+        ab_parts.push(format!("\u{2191}{}", status.ahead));
+    }
+    if status.behind > 0 {
+        ab_parts.push(format!("\u{2193}{}", status.behind));
+    }
+
+    if ab_parts.is_empty() {
+        state
+    } else {
+        format!("{} {}", state, ab_parts.join(" "))
     }
-    let text = String::from_utf8_lossy(&output.stdout);
-    Ok(text.trim().parse::<i64>().unwrap_or(0))
 }
 
 fn load_config(path: &Path) -> Result<Config> {
@@ -279,6 +524,59 @@ fn sprout_paths() -> Result<SproutPaths> {
     })
 }
 
+/// Bootstraps a freshly created worktree: copies configured files in from the
+/// source repo, then runs configured post-create hooks. Bails on the first
+/// failure so `create_worktree` can roll back the worktree it just added.
+fn provision_worktree(
+    repo_root: &Path,
+    worktree_path: &Path,
+    copy_globs: &[String],
+    post_create_hooks: &[String],
+) -> Result<()> {
+    copy_configured_files(repo_root, worktree_path, copy_globs)?;
+    run_post_create_hooks(worktree_path, post_create_hooks)
+}
+
+fn copy_configured_files(repo_root: &Path, worktree_path: &Path, patterns: &[String]) -> Result<()> {
+    for pattern in patterns {
+        let full_pattern = repo_root.join(pattern);
+        let full_pattern = full_pattern.to_string_lossy().to_string();
+        let matches = glob::glob(&full_pattern)
+            .with_context(|| format!("invalid copy pattern: {pattern}"))?;
+        for entry in matches {
+            let src = entry.with_context(|| format!("failed to read glob match for {pattern}"))?;
+            if !src.is_file() {
+                continue;
+            }
+            let relative = src.strip_prefix(repo_root).unwrap_or(&src);
+            let dest = worktree_path.join(relative);
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(&src, &dest).with_context(|| {
+                format!("failed to copy {} to {}", src.display(), dest.display())
+            })?;
+        }
+    }
+    Ok(())
+}
+
+fn run_post_create_hooks(worktree_path: &Path, commands: &[String]) -> Result<()> {
+    let shell = env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
+    for command in commands {
+        let status = Command::new(&shell)
+            .arg("-c")
+            .arg(command)
+            .current_dir(worktree_path)
+            .status()
+            .with_context(|| format!("failed to run post-create hook: {command}"))?;
+        if !status.success() {
+            bail!("post-create hook failed: {command}");
+        }
+    }
+    Ok(())
+}
+
 fn launch_shell(path: &Path) -> Result<()> {
     let shell = env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
     let status = Command::new(shell)
@@ -311,6 +609,15 @@ fn format_ts(ts: i64) -> String {
     dt.format("%Y-%m-%d %H:%M:%S").to_string()
 }
 
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split('\n')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
 fn canonicalize_string(path: &str) -> Option<String> {
     fs::canonicalize(path)
         .ok()
@@ -339,6 +646,19 @@ fn config_get(path: &Path, key: &str) -> Result<()> {
             println!("{value}");
             Ok(())
         }
+        "git_backend" => {
+            let value = config.git_backend.unwrap_or_else(|| "process".to_string());
+            println!("{value}");
+            Ok(())
+        }
+        "copy_globs" => {
+            println!("{}", config.copy_globs.join("\n"));
+            Ok(())
+        }
+        "post_create_hooks" => {
+            println!("{}", config.post_create_hooks.join("\n"));
+            Ok(())
+        }
         _ => bail!("unknown config key: {}", key),
     }
 }
@@ -349,8 +669,143 @@ fn config_set(path: &Path, key: &str, value: &str) -> Result<()> {
         "branch_prefix" => {
             config.branch_prefix = Some(value.to_string());
         }
+        "git_backend" => {
+            if value != "process" && value != "libgit2" {
+                bail!("unknown git backend: {} (expected process or libgit2)", value);
+            }
+            config.git_backend = Some(value.to_string());
+        }
+        "copy_globs" => {
+            config.copy_globs = split_list(value);
+        }
+        "post_create_hooks" => {
+            config.post_create_hooks = split_list(value);
+        }
         _ => bail!("unknown config key: {}", key),
     }
     save_config(path, &config)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeBackend;
+
+    impl GitBackend for FakeBackend {
+        fn repo_root(&self) -> Result<PathBuf> {
+            unimplemented!()
+        }
+        fn add_worktree_from(&self, _repo: &Path, _path: &Path, _branch: &str, _base: &str) -> Result<()> {
+            unimplemented!()
+        }
+        fn checkout_worktree(&self, _repo: &Path, _path: &Path, _reference: &str) -> Result<String> {
+            unimplemented!()
+        }
+        fn remove_worktree(&self, _repo: &Path, _path: &str) -> Result<()> {
+            unimplemented!()
+        }
+        fn remove_worktree_force(&self, _repo: &Path, _path: &str) -> Result<()> {
+            unimplemented!()
+        }
+        fn last_commit_ts(&self, worktree_path: &str) -> Result<i64> {
+            Ok(worktree_path.len() as i64)
+        }
+        fn status(&self, _worktree_path: &str) -> Result<GitStatus> {
+            Ok(GitStatus {
+                ahead: 0,
+                behind: 0,
+                tracked: 0,
+                untracked: 0,
+            })
+        }
+        fn list_branches(&self, _repo: &Path) -> Result<Vec<BranchInfo>> {
+            unimplemented!()
+        }
+        fn list_git_worktrees(&self, _repo: &Path) -> Result<Vec<GitWorktreeRecord>> {
+            unimplemented!()
+        }
+    }
+
+    fn entry(name: &str, path: &str, branch: &str) -> WorktreeEntry {
+        WorktreeEntry {
+            name: name.to_string(),
+            path: path.to_string(),
+            source_repo: "/repo".to_string(),
+            branch: branch.to_string(),
+            base: "main".to_string(),
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn refresh_entries_returns_one_row_per_entry() {
+        let entries = vec![entry("a", "/tmp/a", "main"), entry("b", "/tmp/b", "main")];
+        let rows = refresh_entries(&FakeBackend, entries);
+
+        let mut names: Vec<_> = rows.iter().map(|(_, _, entry)| entry.name.clone()).collect();
+        names.sort();
+        assert_eq!(names, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn reconcile_drops_entries_unknown_to_git() {
+        let entries = vec![entry("gone", "/tmp/gone", "main")];
+        let result = reconcile_repo_worktrees("/repo", "/repo", Vec::new(), entries, 0);
+
+        assert!(result.kept.is_empty());
+        assert_eq!(result.removed, vec!["gone".to_string()]);
+        assert!(result.updated.is_empty());
+        assert!(result.imported.is_empty());
+    }
+
+    #[test]
+    fn reconcile_drops_prunable_entries_whose_directory_is_gone() {
+        let missing_path = "/tmp/sprout-test-does-not-exist";
+        let entries = vec![entry("gone", missing_path, "main")];
+        let git_worktrees = vec![GitWorktreeRecord {
+            path: missing_path.to_string(),
+            branch: Some("main".to_string()),
+        }];
+        let result = reconcile_repo_worktrees("/repo", "/repo", git_worktrees, entries, 0);
+
+        assert!(result.kept.is_empty());
+        assert_eq!(result.removed, vec!["gone".to_string()]);
+    }
+
+    #[test]
+    fn reconcile_updates_stale_branch_and_imports_unknown_worktree() {
+        let existing = entry("known", "/tmp", "old-branch");
+        let git_worktrees = vec![
+            GitWorktreeRecord {
+                path: "/tmp".to_string(),
+                branch: Some("new-branch".to_string()),
+            },
+            GitWorktreeRecord {
+                path: "/tmp/new".to_string(),
+                branch: Some("feature".to_string()),
+            },
+        ];
+        let result = reconcile_repo_worktrees("/repo", "/repo", git_worktrees, vec![existing], 42);
+
+        assert!(result.removed.is_empty());
+        assert_eq!(result.updated, vec!["known: old-branch -> new-branch".to_string()]);
+
+        let mut names: Vec<_> = result.kept.iter().map(|entry| entry.name.clone()).collect();
+        names.sort();
+        assert_eq!(names, vec!["feature", "known"]);
+    }
+
+    #[test]
+    fn reconcile_skips_main_worktree() {
+        let git_worktrees = vec![GitWorktreeRecord {
+            path: "/repo".to_string(),
+            branch: Some("main".to_string()),
+        }];
+        let result = reconcile_repo_worktrees("/repo", "/repo", git_worktrees, Vec::new(), 0);
+
+        assert!(result.kept.is_empty());
+        assert!(result.imported.is_empty());
+    }
+}